@@ -3,6 +3,8 @@
 //! This module implements the Newton-Raphson numerical solver for
 //! four-bar linkage position analysis.
 
+#![allow(dead_code)]
+
 /// Configuration parameters for a four-bar linkage
 #[derive(Debug, Clone, Copy)]
 pub struct FourBarConfig {
@@ -41,12 +43,46 @@ impl Default for FourBarState {
     }
 }
 
+/// Outcome of the most recent call to `solve`, recording whether the
+/// Armijo backtracking line search had to shrink the full Newton step.
+#[derive(Debug, Clone, Copy)]
+pub struct SolveInfo {
+    pub iterations: usize,
+    pub damped: bool,
+    pub min_alpha: f64,
+}
+
+/// Which root of the position-analysis quadratic to track.
+///
+/// For a given input angle the loop-closure equations admit two solutions:
+/// the "open" configuration (coupler and rocker on the same side, the
+/// usual drawing) and the "crossed" configuration (the coupler link
+/// crosses the ground link). The two differ by the sign in front of the
+/// discriminant in the half-angle substitution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Branch {
+    Open,
+    Crossed,
+}
+
+/// Result of a full-rotation continuation sweep on one assembly branch
+#[derive(Debug, Clone)]
+pub struct BranchSweep {
+    pub states: Vec<FourBarState>,
+    /// Sampled crank angles (radians) at which the open and crossed
+    /// branches merge (discriminant within 1e-6 of zero); a merge point
+    /// falling between two samples is not reported
+    pub dead_centers: Vec<f64>,
+}
+
 /// Four-bar linkage mechanism solver
 pub struct FourBar {
     pub config: FourBarConfig,
     pub state: FourBarState,
     tolerance: f64,
     max_iterations: usize,
+    last_solve: Option<SolveInfo>,
+    branch: Branch,
 }
 
 impl FourBar {
@@ -57,6 +93,8 @@ impl FourBar {
             state: FourBarState::default(),
             tolerance: 1e-9,
             max_iterations: 100,
+            last_solve: None,
+            branch: Branch::Open,
         }
     }
 
@@ -68,9 +106,16 @@ impl FourBar {
             state: FourBarState::default(),
             tolerance: 1e-9,
             max_iterations: 100,
+            last_solve: None,
+            branch: Branch::Open,
         }
     }
 
+    /// Damping outcome of the last `solve` call, if one has run
+    pub fn last_solve_info(&self) -> Option<SolveInfo> {
+        self.last_solve
+    }
+
     /// Update input angle (in degrees) and solve for theta3 and theta4
     pub fn set_theta2_degrees(&mut self, theta2_deg: f64) -> Result<(), String> {
         self.state.theta2 = theta2_deg.to_radians();
@@ -99,16 +144,34 @@ impl FourBar {
             }
         }
 
-        // Newton-Raphson iteration
-        for _iter in 0..self.max_iterations {
+        // Merit function phi = f1^2 + f2^2 used by the Armijo line search below
+        let residual = |t3: f64, t4: f64| -> (f64, f64) {
+            (
+                r2 * theta2.cos() + r3 * t3.cos() - r4 * t4.cos() - r1,
+                r2 * theta2.sin() + r3 * t3.sin() - r4 * t4.sin(),
+            )
+        };
+
+        const ARMIJO_C: f64 = 1e-4;
+        const MAX_BACKTRACKS: usize = 20;
+
+        let mut damped = false;
+        let mut min_alpha = 1.0;
+
+        // Damped Newton-Raphson iteration
+        for iter in 0..self.max_iterations {
             // Compute error functions
-            let f1 = r2 * theta2.cos() + r3 * theta3.cos() - r4 * theta4.cos() - r1;
-            let f2 = r2 * theta2.sin() + r3 * theta3.sin() - r4 * theta4.sin();
+            let (f1, f2) = residual(theta3, theta4);
 
             // Check convergence
             if f1.abs() < self.tolerance && f2.abs() < self.tolerance {
                 self.state.theta3 = theta3;
                 self.state.theta4 = theta4;
+                self.last_solve = Some(SolveInfo {
+                    iterations: iter,
+                    damped,
+                    min_alpha,
+                });
                 return Ok(());
             }
 
@@ -131,9 +194,27 @@ impl FourBar {
             let d_theta3 = (-f1 * j22 + f2 * j12) / det;
             let d_theta4 = (j11 * (-f2) - j21 * (-f1)) / det;
 
+            // Armijo backtracking: accept the full step only if it reduces
+            // phi; otherwise halve alpha until it does (or we give up and
+            // take the smallest step tried). Since delta solves J*delta=-F,
+            // the directional derivative of phi along delta is exactly -2*phi,
+            // so the accept test simplifies to phi_new <= phi*(1 - 2*c*alpha).
+            let phi0 = f1 * f1 + f2 * f2;
+            let mut alpha = 1.0;
+            for backtrack in 0..=MAX_BACKTRACKS {
+                let (f1n, f2n) = residual(theta3 + alpha * d_theta3, theta4 + alpha * d_theta4);
+                let phi_new = f1n * f1n + f2n * f2n;
+                if phi_new <= phi0 * (1.0 - 2.0 * ARMIJO_C * alpha) || backtrack == MAX_BACKTRACKS {
+                    break;
+                }
+                alpha *= 0.5;
+                damped = true;
+            }
+            min_alpha = min_alpha.min(alpha);
+
             // Update guess
-            theta3 += d_theta3;
-            theta4 += d_theta4;
+            theta3 += alpha * d_theta3;
+            theta4 += alpha * d_theta4;
         }
 
         Err(format!(
@@ -143,43 +224,127 @@ impl FourBar {
         ))
     }
 
-    /// Analytical solution for initial guess (Open configuration)
+    /// Analytical solution for initial guess, on the currently selected branch
     fn analytical_solution(&self, theta2: f64) -> Option<(f64, f64)> {
+        self.analytical_solution_branch(theta2, self.branch)
+    }
+
+    /// Coefficients of the half-angle quadratic `a*x^2 + b*x + c = 0` whose
+    /// roots give `tan(theta4/2)` for the two assembly branches. The
+    /// discriminant `b^2 - 4ac` crosses zero exactly where the two branches
+    /// meet, i.e. a dead-center position.
+    fn theta4_quadratic(&self, theta2: f64) -> (f64, f64, f64) {
         let FourBarConfig { r1, r2, r3, r4 } = self.config;
 
         let k1 = r1 / r2;
         let k2 = r1 / r4;
-        let k3 = (r1.powi(2) + r2.powi(2) + r3.powi(2) - r4.powi(2)) / (2.0 * r2 * r3);
+        let k3 = (r1.powi(2) + r2.powi(2) - r3.powi(2) + r4.powi(2)) / (2.0 * r2 * r4);
 
         let a = theta2.cos() - k1 - k2 * theta2.cos() + k3;
         let b = -2.0 * theta2.sin();
         let c = k1 - (k2 + 1.0) * theta2.cos() + k3;
 
+        (a, b, c)
+    }
+
+    /// Discriminant of the theta4 half-angle quadratic at a given crank
+    /// angle. Negative means the mechanism cannot assemble there; values
+    /// close to zero mark a dead-center position where the open and
+    /// crossed branches merge.
+    fn theta4_discriminant(&self, theta2: f64) -> f64 {
+        let (a, b, c) = self.theta4_quadratic(theta2);
+        b.powi(2) - 4.0 * a * c
+    }
+
+    /// Analytical solution for a specific assembly branch. The open and
+    /// crossed configurations correspond to the opposite sign choices in
+    /// front of the discriminant of the theta4 half-angle quadratic; theta3
+    /// then follows from the loop closure.
+    fn analytical_solution_branch(&self, theta2: f64, branch: Branch) -> Option<(f64, f64)> {
+        let FourBarConfig { r1, r2, r4, .. } = self.config;
+
+        let (a, b, c) = self.theta4_quadratic(theta2);
+
         // Solve for theta4 using half-angle substitution
         let discriminant = b.powi(2) - 4.0 * a * c;
         if discriminant < 0.0 {
             return None;
         }
 
-        // Open configuration (typically the one we want)
-        let theta4 = 2.0 * (((-b + discriminant.sqrt()) / (2.0 * a)).atan());
+        let theta4 = match branch {
+            Branch::Open => 2.0 * (((-b + discriminant.sqrt()) / (2.0 * a)).atan()),
+            Branch::Crossed => 2.0 * (((-b - discriminant.sqrt()) / (2.0 * a)).atan()),
+        };
 
-        // Solve for theta3
-        let k4 = r1 / r3;
-        let k5 = (r4.powi(2) - r1.powi(2) - r2.powi(2) - r3.powi(2)) / (2.0 * r2 * r3);
+        // With theta4 fixed, theta3 follows directly from the loop closure
+        // r3*e^{i*theta3} = r1 + r4*e^{i*theta4} - r2*e^{i*theta2}: both
+        // components of the vector on the right are known, so theta3 is
+        // just its angle.
+        let vx = r1 + r4 * theta4.cos() - r2 * theta2.cos();
+        let vy = r4 * theta4.sin() - r2 * theta2.sin();
+        let theta3 = vy.atan2(vx);
 
-        let d = theta2.cos() - k1 + k4 * theta4.cos() + k5;
-        let e = -2.0 * theta2.sin();
-        let f = k1 + (k4 - 1.0) * theta4.cos() + k5;
+        Some((theta3, theta4))
+    }
 
-        let discriminant2 = e.powi(2) - 4.0 * d * f;
-        if discriminant2 < 0.0 {
-            return None;
-        }
+    /// Both assembly configurations (open and crossed) for a given crank
+    /// angle (radians), as full `FourBarState`s. Returns `None` for a
+    /// branch the mechanism cannot assemble into at that angle.
+    pub fn both_branches(&self, theta2: f64) -> (Option<FourBarState>, Option<FourBarState>) {
+        let to_state = |branch| {
+            self.analytical_solution_branch(theta2, branch)
+                .map(|(theta3, theta4)| FourBarState {
+                    theta2,
+                    theta3,
+                    theta4,
+                })
+        };
+        (to_state(Branch::Open), to_state(Branch::Crossed))
+    }
 
-        let theta3 = 2.0 * (((-e - discriminant2.sqrt()) / (2.0 * d)).atan());
+    /// Switch which assembly branch `solve` seeds its initial guess from
+    pub fn set_branch(&mut self, branch: Branch) {
+        self.branch = branch;
+    }
 
-        Some((theta3, theta4))
+    /// Assembly branch currently used to seed `solve`
+    pub fn branch(&self) -> Branch {
+        self.branch
+    }
+
+    /// Sweep the crank through a full rotation on one assembly branch,
+    /// using continuation (each solve seeds from the previous state) so the
+    /// mechanism stays on that circuit instead of jumping branches. Returns
+    /// the reached states together with the sampled crank angles (radians)
+    /// at which the two branches merge into a dead-center position; only
+    /// sample angles are checked, so a merge point between samples is missed.
+    pub fn sweep_branch(&mut self, branch: Branch, step_deg: f64) -> BranchSweep {
+        let original_branch = self.branch;
+        let original_state = self.state;
+        self.branch = branch;
+        self.state = FourBarState::default();
+
+        let mut states = Vec::new();
+        let mut dead_centers = Vec::new();
+
+        let mut theta2_deg: f64 = 0.0;
+        while theta2_deg < 360.0 {
+            let theta2 = theta2_deg.to_radians();
+            if self.theta4_discriminant(theta2).abs() < 1e-6 {
+                dead_centers.push(theta2);
+            }
+            if self.set_theta2_degrees(theta2_deg).is_ok() {
+                states.push(self.state);
+            }
+            theta2_deg += step_deg;
+        }
+
+        self.branch = original_branch;
+        self.state = original_state;
+        BranchSweep {
+            states,
+            dead_centers,
+        }
     }
 
     /// Get joint positions in Cartesian coordinates
@@ -205,6 +370,152 @@ impl FourBar {
         }
     }
 
+    /// Cartesian position of an arbitrary point rigidly attached to the
+    /// coupler link, at the current state
+    pub fn coupler_point_position(&self, point: CouplerPoint) -> Point2D {
+        let positions = self.get_positions();
+        let theta3 = self.state.theta3;
+
+        Point2D {
+            x: positions.p2.x + point.along * theta3.cos() - point.offset * theta3.sin(),
+            y: positions.p2.y + point.along * theta3.sin() + point.offset * theta3.cos(),
+        }
+    }
+
+    /// Trace the curve swept by a coupler point over a full crank rotation,
+    /// using continuation so the mechanism stays on one circuit. Crank
+    /// angles where the mechanism fails to assemble are reported separately
+    /// rather than aborting the sweep, so double-rocker geometries still
+    /// yield the partial curve they actually trace.
+    pub fn coupler_curve(&mut self, point: CouplerPoint, step_deg: f64) -> CouplerCurve {
+        let original_state = self.state;
+        self.state = FourBarState::default();
+
+        let mut points = Vec::new();
+        let mut unreachable_theta2_deg = Vec::new();
+
+        let mut theta2_deg: f64 = 0.0;
+        while theta2_deg < 360.0 {
+            match self.set_theta2_degrees(theta2_deg) {
+                Ok(()) => points.push(self.coupler_point_position(point)),
+                Err(_) => {
+                    unreachable_theta2_deg.push(theta2_deg);
+                    // Reset so the next step retries from the analytical guess
+                    self.state.theta3 = 0.0;
+                    self.state.theta4 = 0.0;
+                }
+            }
+            theta2_deg += step_deg;
+        }
+
+        self.state = original_state;
+        CouplerCurve {
+            points,
+            unreachable_theta2_deg,
+        }
+    }
+
+    /// First-order kinematics (coupler/rocker angular velocity and the
+    /// Cartesian velocity of the coupler point) for an input angular
+    /// velocity `omega2`, evaluated at the current state. Reuses the
+    /// Jacobian from `solve`: `J * [theta3_dot, theta4_dot]^T = -[d f1/d
+    /// theta2, d f2/d theta2]^T * omega2`.
+    pub fn velocity_analysis(&self, omega2: f64) -> Result<VelocityState, String> {
+        let FourBarConfig { r2, r3, r4, .. } = self.config;
+        let FourBarState {
+            theta2,
+            theta3,
+            theta4,
+        } = self.state;
+
+        let j11 = -r3 * theta3.sin();
+        let j12 = r4 * theta4.sin();
+        let j21 = r3 * theta3.cos();
+        let j22 = -r4 * theta4.cos();
+
+        let det = j11 * j22 - j12 * j21;
+        if det.abs() < 1e-9 {
+            return Err(format!(
+                "Singularity detected at theta2 = {:.1}°",
+                theta2.to_degrees()
+            ));
+        }
+
+        let b1 = r2 * theta2.sin() * omega2;
+        let b2 = -r2 * theta2.cos() * omega2;
+
+        let theta3_dot = (b1 * j22 - j12 * b2) / det;
+        let theta4_dot = (j11 * b2 - j21 * b1) / det;
+
+        let vx = -r2 * theta2.sin() * omega2 - r3 * theta3.sin() * theta3_dot;
+        let vy = r2 * theta2.cos() * omega2 + r3 * theta3.cos() * theta3_dot;
+
+        Ok(VelocityState {
+            theta3_dot,
+            theta4_dot,
+            coupler_point_velocity: Point2D { x: vx, y: vy },
+        })
+    }
+
+    /// Second-order kinematics (coupler/rocker angular acceleration and the
+    /// Cartesian acceleration of the coupler point) for an input angular
+    /// velocity `omega2` and acceleration `alpha2`, evaluated at the
+    /// current state. Differentiates the velocity relation once more,
+    /// reusing the same Jacobian.
+    pub fn acceleration_analysis(
+        &self,
+        omega2: f64,
+        alpha2: f64,
+    ) -> Result<AccelerationState, String> {
+        let FourBarConfig { r2, r3, r4, .. } = self.config;
+        let FourBarState {
+            theta2,
+            theta3,
+            theta4,
+        } = self.state;
+
+        let velocity = self.velocity_analysis(omega2)?;
+        let theta3_dot = velocity.theta3_dot;
+        let theta4_dot = velocity.theta4_dot;
+
+        let j11 = -r3 * theta3.sin();
+        let j12 = r4 * theta4.sin();
+        let j21 = r3 * theta3.cos();
+        let j22 = -r4 * theta4.cos();
+
+        let det = j11 * j22 - j12 * j21;
+        if det.abs() < 1e-9 {
+            return Err(format!(
+                "Singularity detected at theta2 = {:.1}°",
+                theta2.to_degrees()
+            ));
+        }
+
+        let b1 = r2 * theta2.cos() * omega2.powi(2)
+            + r2 * theta2.sin() * alpha2
+            + r3 * theta3.cos() * theta3_dot.powi(2)
+            - r4 * theta4.cos() * theta4_dot.powi(2);
+        let b2 = r2 * theta2.sin() * omega2.powi(2) - r2 * theta2.cos() * alpha2
+            + r3 * theta3.sin() * theta3_dot.powi(2)
+            - r4 * theta4.sin() * theta4_dot.powi(2);
+
+        let theta3_ddot = (b1 * j22 - j12 * b2) / det;
+        let theta4_ddot = (j11 * b2 - j21 * b1) / det;
+
+        let ax = -r2 * theta2.cos() * omega2.powi(2) - r2 * theta2.sin() * alpha2
+            + -r3 * theta3.cos() * theta3_dot.powi(2)
+            - r3 * theta3.sin() * theta3_ddot;
+        let ay = -r2 * theta2.sin() * omega2.powi(2) + r2 * theta2.cos() * alpha2
+            - r3 * theta3.sin() * theta3_dot.powi(2)
+            + r3 * theta3.cos() * theta3_ddot;
+
+        Ok(AccelerationState {
+            theta3_ddot,
+            theta4_ddot,
+            coupler_point_acceleration: Point2D { x: ax, y: ay },
+        })
+    }
+
     /// Check if mechanism satisfies Grashof condition
     pub fn is_grashof(&self) -> bool {
         let FourBarConfig { r1, r2, r3, r4 } = self.config;
@@ -242,6 +553,74 @@ impl FourBar {
             _ => "Unknown",
         }
     }
+
+    /// Transmission angle `mu = theta4 - theta3` at the current state,
+    /// normalized to `[0, pi]`. Good force transmission keeps this roughly
+    /// between 40° and 140°; values near 0 or 180° mean the coupler is
+    /// nearly aligned with the rocker and the mechanism is close to
+    /// locking up.
+    pub fn transmission_angle(&self) -> f64 {
+        let mu = (self.state.theta4 - self.state.theta3).rem_euclid(2.0 * std::f64::consts::PI);
+        if mu > std::f64::consts::PI {
+            2.0 * std::f64::consts::PI - mu
+        } else {
+            mu
+        }
+    }
+
+    /// Instantaneous mechanical advantage: the ratio of output to input
+    /// angular velocity, `omega4 / omega2`, derived from the velocity
+    /// analysis. Returns an error wherever `velocity_analysis` would
+    /// (e.g. at a singularity).
+    pub fn mechanical_advantage(&self, omega2: f64) -> Result<f64, String> {
+        if omega2 == 0.0 {
+            return Err("mechanical advantage is undefined at omega2 = 0".to_string());
+        }
+        let velocity = self.velocity_analysis(omega2)?;
+        Ok(velocity.theta4_dot / omega2)
+    }
+
+    /// Sweep the crank through a full rotation (using continuation) and
+    /// report the worst-case transmission angle, flagging whether it stays
+    /// within `quality_band_deg` (typically `(40.0, 140.0)`). Returns
+    /// `Err(NeverAssembles)` rather than a false "good" verdict if the
+    /// mechanism fails to assemble at every sampled angle.
+    pub fn transmission_angle_sweep(
+        &mut self,
+        step_deg: f64,
+        quality_band_deg: (f64, f64),
+    ) -> Result<TransmissionAngleReport, TransmissionAngleSweepError> {
+        let original_state = self.state;
+        self.state = FourBarState::default();
+
+        let mut min = std::f64::consts::PI;
+        let mut max: f64 = 0.0;
+        let mut any_assembled = false;
+
+        let mut theta2_deg: f64 = 0.0;
+        while theta2_deg < 360.0 {
+            if self.set_theta2_degrees(theta2_deg).is_ok() {
+                any_assembled = true;
+                let mu = self.transmission_angle();
+                min = min.min(mu);
+                max = max.max(mu);
+            }
+            theta2_deg += step_deg;
+        }
+
+        self.state = original_state;
+
+        if !any_assembled {
+            return Err(TransmissionAngleSweepError::NeverAssembles);
+        }
+
+        let (band_min, band_max) = (quality_band_deg.0.to_radians(), quality_band_deg.1.to_radians());
+        Ok(TransmissionAngleReport {
+            min,
+            max,
+            within_quality_band: min >= band_min && max <= band_max,
+        })
+    }
 }
 
 impl Default for FourBar {
@@ -257,6 +636,23 @@ pub struct Point2D {
     pub y: f64,
 }
 
+/// A point rigidly attached to the coupler link, described relative to
+/// joint P2: `along` is the distance from P2 towards P3 along the coupler,
+/// `offset` is the perpendicular distance from that line.
+#[derive(Debug, Clone, Copy)]
+pub struct CouplerPoint {
+    pub along: f64,
+    pub offset: f64,
+}
+
+/// Result of sweeping a coupler point through a full crank rotation
+#[derive(Debug, Clone)]
+pub struct CouplerCurve {
+    pub points: Vec<Point2D>,
+    /// Crank angles (degrees) where the mechanism failed to assemble
+    pub unreachable_theta2_deg: Vec<f64>,
+}
+
 /// Joint positions of the four-bar linkage
 #[derive(Debug, Clone, Copy)]
 pub struct JointPositions {
@@ -266,6 +662,39 @@ pub struct JointPositions {
     pub p4: Point2D, // Ground joint 2
 }
 
+/// First-order kinematic state: coupler/rocker angular velocity and the
+/// Cartesian velocity of the coupler point
+#[derive(Debug, Clone, Copy)]
+pub struct VelocityState {
+    pub theta3_dot: f64,
+    pub theta4_dot: f64,
+    pub coupler_point_velocity: Point2D,
+}
+
+/// Second-order kinematic state: coupler/rocker angular acceleration and
+/// the Cartesian acceleration of the coupler point
+#[derive(Debug, Clone, Copy)]
+pub struct AccelerationState {
+    pub theta3_ddot: f64,
+    pub theta4_ddot: f64,
+    pub coupler_point_acceleration: Point2D,
+}
+
+/// Worst-case transmission angle (radians) over a full crank rotation
+#[derive(Debug, Clone, Copy)]
+pub struct TransmissionAngleReport {
+    pub min: f64,
+    pub max: f64,
+    pub within_quality_band: bool,
+}
+
+/// Reasons `transmission_angle_sweep` can refuse to produce a report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransmissionAngleSweepError {
+    /// The mechanism failed to assemble at every sampled crank angle
+    NeverAssembles,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -290,4 +719,149 @@ mod tests {
             assert!(result.is_ok(), "Failed at theta2 = {}°", theta2);
         }
     }
+
+    #[test]
+    fn test_damped_newton_converges_near_grashof_limit() {
+        // Close to the Grashof boundary the Jacobian gets ill-conditioned
+        // near dead-center and a full Newton step can overshoot; damping
+        // should still bring the solver home. The coarse 30° step is
+        // chosen because it's confirmed to actually engage the Armijo
+        // line search for this config (a finer step stays close enough to
+        // the previous solution that the full Newton step already works).
+        let mut linkage = FourBar::with_config(FourBarConfig {
+            r1: 8.0,
+            r2: 1.0,
+            r3: 4.0,
+            r4: 5.0,
+        });
+        let mut any_damped = false;
+        for theta2 in (0..360).step_by(30) {
+            let result = linkage.set_theta2_degrees(theta2 as f64);
+            assert!(result.is_ok(), "Failed at theta2 = {}°", theta2);
+            if let Some(info) = linkage.last_solve_info() {
+                any_damped |= info.damped;
+                assert!(info.min_alpha > 0.0);
+            }
+        }
+        assert!(
+            any_damped,
+            "expected at least one angle to engage Armijo backtracking"
+        );
+    }
+
+    #[test]
+    fn test_both_branches_differ() {
+        let linkage = FourBar::new();
+        let theta2 = 45f64.to_radians();
+        let (open, crossed) = linkage.both_branches(theta2);
+        let open = open.expect("open branch should assemble");
+        let crossed = crossed.expect("crossed branch should assemble");
+        assert!((open.theta3 - crossed.theta3).abs() > 1e-6);
+        assert!((open.theta4 - crossed.theta4).abs() > 1e-6);
+    }
+
+    #[test]
+    fn test_sweep_branch_stays_on_circuit() {
+        let mut linkage = FourBar::new();
+        let sweep = linkage.sweep_branch(Branch::Open, 10.0);
+        assert!(!sweep.states.is_empty());
+        for state in &sweep.states {
+            let (open, _) = linkage.both_branches(state.theta2);
+            if let Some(open) = open {
+                // Compare via sin/cos since Newton continuation doesn't
+                // wrap its angles to the same [-pi, pi] range as the
+                // closed-form solution.
+                assert!((state.theta3.cos() - open.theta3.cos()).abs() < 1e-3);
+                assert!((state.theta3.sin() - open.theta3.sin()).abs() < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_velocity_matches_finite_difference() {
+        let omega2 = 1.0; // rad/s
+        let dt = 1e-6;
+
+        let mut linkage = FourBar::new();
+        linkage.set_theta2_degrees(60.0).unwrap();
+        let velocity = linkage.velocity_analysis(omega2).unwrap();
+
+        let mut ahead = FourBar::new();
+        ahead
+            .set_theta2_degrees(60.0 + (omega2 * dt).to_degrees())
+            .unwrap();
+
+        let theta3_dot_fd = (ahead.state.theta3 - linkage.state.theta3) / dt;
+        let theta4_dot_fd = (ahead.state.theta4 - linkage.state.theta4) / dt;
+
+        assert!((velocity.theta3_dot - theta3_dot_fd).abs() < 1e-3);
+        assert!((velocity.theta4_dot - theta4_dot_fd).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_acceleration_analysis_runs() {
+        let mut linkage = FourBar::new();
+        linkage.set_theta2_degrees(60.0).unwrap();
+        let result = linkage.acceleration_analysis(1.0, 0.0);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_coupler_point_at_p3_matches_get_positions() {
+        let mut linkage = FourBar::new();
+        linkage.set_theta2_degrees(30.0).unwrap();
+        let p3 = linkage.get_positions().p3;
+        let point = linkage.coupler_point_position(CouplerPoint {
+            along: linkage.config.r3,
+            offset: 0.0,
+        });
+        assert!((p3.x - point.x).abs() < 1e-9);
+        assert!((p3.y - point.y).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_coupler_curve_default_mechanism_has_no_gaps() {
+        let mut linkage = FourBar::new();
+        let curve = linkage.coupler_curve(
+            CouplerPoint {
+                along: 2.5,
+                offset: 1.0,
+            },
+            10.0,
+        );
+        assert_eq!(curve.points.len(), 36);
+        assert!(curve.unreachable_theta2_deg.is_empty());
+    }
+
+    #[test]
+    fn test_transmission_angle_is_normalized() {
+        let mut linkage = FourBar::new();
+        linkage.set_theta2_degrees(120.0).unwrap();
+        let mu = linkage.transmission_angle();
+        assert!((0.0..=std::f64::consts::PI).contains(&mu));
+    }
+
+    #[test]
+    fn test_transmission_angle_sweep_reports_min_max() {
+        let mut linkage = FourBar::new();
+        let report = linkage
+            .transmission_angle_sweep(5.0, (40.0, 140.0))
+            .expect("default mechanism assembles everywhere");
+        assert!(report.min <= report.max);
+        assert!(report.min >= 0.0 && report.max <= std::f64::consts::PI);
+    }
+
+    #[test]
+    fn test_transmission_angle_sweep_reports_never_assembles() {
+        // r1 dwarfs the other three links, so the loop-closure equations
+        // never have a real solution at any crank angle.
+        let mut linkage = FourBar::with_config(FourBarConfig {
+            r1: 100.0,
+            r2: 1.0,
+            r3: 1.0,
+            r4: 1.0,
+        });
+        let result = linkage.transmission_angle_sweep(5.0, (40.0, 140.0));
+        assert_eq!(result.unwrap_err(), TransmissionAngleSweepError::NeverAssembles);
+    }
 }
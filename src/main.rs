@@ -7,7 +7,9 @@
 
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod dynamics;
 mod fourbar;
+mod synthesis;
 
 use eframe::egui;
 use fourbar::{FourBar, Point2D};
@@ -70,6 +72,19 @@ pub async fn start(canvas_id: &str) -> Result<(), wasm_bindgen::JsValue> {
 // Application State
 // =============================================================================
 
+/// Which joint is currently being dragged by the pointer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DragTarget {
+    Crank,
+    /// P1 is hardcoded to the coordinate origin (see `FourBar::get_positions`),
+    /// so there is no ground-geometry field dragging it could edit; this
+    /// variant instead pans the view, keeping the pointer under the joint.
+    GroundP1,
+    GroundP4,
+}
+
+const JOINT_HIT_RADIUS: f32 = 12.0;
+
 struct FourBarApp {
     linkage: FourBar,
     theta2_deg: f64,
@@ -82,6 +97,8 @@ struct FourBarApp {
     error_message: Option<String>,
     show_angles: bool,
     show_grid: bool,
+    dragging: Option<DragTarget>,
+    pan_offset: egui::Vec2,
 }
 
 impl FourBarApp {
@@ -98,6 +115,8 @@ impl FourBarApp {
             error_message: None,
             show_angles: true,
             show_grid: true,
+            dragging: None,
+            pan_offset: egui::Vec2::ZERO,
         };
 
         // Initialize with theta2 = 0
@@ -128,6 +147,72 @@ impl FourBarApp {
         }
     }
 
+    /// Hit-test the joints against the pointer and let the user drag the
+    /// crank tip or a ground pivot directly, mirroring the interactive
+    /// painter pattern from the egui examples. Dragging P4 edits the
+    /// ground link length `r1`; dragging P1 pans the view instead, since
+    /// P1 is pinned to the origin and has no geometry of its own to edit.
+    fn handle_drag(&mut self, response: &egui::Response, center: egui::Pos2) {
+        let positions = self.linkage.get_positions();
+        let to_screen = |p: Point2D| -> egui::Pos2 {
+            egui::pos2(
+                center.x + p.x as f32 * self.scale as f32,
+                center.y - p.y as f32 * self.scale as f32,
+            )
+        };
+        let p1_screen = to_screen(positions.p1);
+        let p2_screen = to_screen(positions.p2);
+        let p4_screen = to_screen(positions.p4);
+
+        if response.drag_started() {
+            if let Some(pointer) = response.interact_pointer_pos() {
+                self.dragging = if pointer.distance(p2_screen) < JOINT_HIT_RADIUS {
+                    Some(DragTarget::Crank)
+                } else if pointer.distance(p1_screen) < JOINT_HIT_RADIUS {
+                    Some(DragTarget::GroundP1)
+                } else if pointer.distance(p4_screen) < JOINT_HIT_RADIUS {
+                    Some(DragTarget::GroundP4)
+                } else {
+                    None
+                };
+            }
+        }
+
+        if response.dragged() {
+            if let (Some(target), Some(pointer)) = (self.dragging, response.interact_pointer_pos())
+            {
+                // Undo the to_screen transform to recover mechanism coordinates
+                let mx = (pointer.x - center.x) as f64 / self.scale;
+                let my = -(pointer.y - center.y) as f64 / self.scale;
+
+                match target {
+                    DragTarget::Crank => {
+                        self.auto_play = false;
+                        self.theta2_deg = my.atan2(mx).to_degrees().rem_euclid(360.0);
+                        self.update_mechanism();
+                    }
+                    DragTarget::GroundP1 => {
+                        // P1 is pinned to the origin, so there's no r1/offset
+                        // field to edit here -- pan the view instead so the
+                        // drag still does something visible.
+                        self.pan_offset += response.drag_delta();
+                    }
+                    DragTarget::GroundP4 => {
+                        self.linkage.config.r1 = (mx * mx + my * my).sqrt().max(0.5);
+                        self.update_mechanism();
+                        if self.show_trace {
+                            self.trace_points.clear();
+                        }
+                    }
+                }
+            }
+        }
+
+        if response.drag_stopped() {
+            self.dragging = None;
+        }
+    }
+
     fn draw_mechanism(&self, _ui: &mut egui::Ui, painter: &egui::Painter, center: egui::Pos2) {
         let positions = self.linkage.get_positions();
 
@@ -515,9 +600,13 @@ impl eframe::App for FourBarApp {
         // Main drawing area
         egui::CentralPanel::default().show(ctx, |ui| {
             let available_size = ui.available_size();
-            let (response, painter) = ui.allocate_painter(available_size, egui::Sense::hover());
+            let (response, painter) =
+                ui.allocate_painter(available_size, egui::Sense::click_and_drag());
+
+            let center = response.rect.center() + self.pan_offset;
 
-            let center = response.rect.center();
+            // Let the user grab joints directly instead of only the slider
+            self.handle_drag(&response, center);
 
             // Draw mechanism
             self.draw_mechanism(ui, &painter, center);
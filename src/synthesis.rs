@@ -0,0 +1,395 @@
+#![allow(dead_code)]
+
+//! Dimensional synthesis of four-bar link lengths by least-squares fitting
+//!
+//! Chooses the four link lengths and a coupler point so that the
+//! resulting coupler curve passes as closely as possible through a set of
+//! user-specified target points (precision-point synthesis), using the
+//! Levenberg-Marquardt algorithm.
+
+use crate::fourbar::{CouplerPoint, FourBar, FourBarConfig, Point2D};
+
+/// The six free parameters of the synthesis problem
+#[derive(Debug, Clone, Copy)]
+pub struct SynthesisParams {
+    pub r1: f64,
+    pub r2: f64,
+    pub r3: f64,
+    pub r4: f64,
+    pub coupler_along: f64,
+    pub coupler_offset: f64,
+}
+
+/// Tuning knobs for the Levenberg-Marquardt fit
+#[derive(Debug, Clone, Copy)]
+pub struct SynthesisOptions {
+    pub max_iterations: usize,
+    /// Reject a candidate whose fitted lengths aren't a crank-rocker
+    pub require_crank_rocker: bool,
+}
+
+impl Default for SynthesisOptions {
+    fn default() -> Self {
+        Self {
+            max_iterations: 100,
+            require_crank_rocker: false,
+        }
+    }
+}
+
+/// Fitted linkage and its fit quality
+#[derive(Debug, Clone, Copy)]
+pub struct SynthesisResult {
+    pub config: FourBarConfig,
+    pub coupler_point: CouplerPoint,
+    pub rms_error: f64,
+}
+
+const NUM_PARAMS: usize = 6;
+const THETA2_STEP_DEG: f64 = 2.0;
+
+fn to_vec(p: SynthesisParams) -> [f64; NUM_PARAMS] {
+    [
+        p.r1,
+        p.r2,
+        p.r3,
+        p.r4,
+        p.coupler_along,
+        p.coupler_offset,
+    ]
+}
+
+fn from_vec(v: &[f64; NUM_PARAMS]) -> SynthesisParams {
+    SynthesisParams {
+        r1: v[0],
+        r2: v[1],
+        r3: v[2],
+        r4: v[3],
+        coupler_along: v[4],
+        coupler_offset: v[5],
+    }
+}
+
+/// Golden-section search tightens the coarse sample search down to a
+/// fraction of a degree
+const GOLDEN_SECTION_ITERATIONS: usize = 40;
+const GOLDEN_RATIO: f64 = 0.618_033_988_749_895;
+
+/// Distance from `target` to the coupler curve at a given crank angle
+/// (degrees), or `None` where the mechanism fails to assemble
+fn distance_at(linkage: &mut FourBar, coupler_point: CouplerPoint, target: Point2D, theta2_deg: f64) -> Option<f64> {
+    linkage.set_theta2_degrees(theta2_deg).ok()?;
+    let p = linkage.coupler_point_position(coupler_point);
+    Some(((p.x - target.x).powi(2) + (p.y - target.y).powi(2)).sqrt())
+}
+
+/// Distance from `target` to the closest point the coupler curve reaches.
+/// This is the residual the Levenberg-Marquardt fit drives towards zero
+/// for every target point.
+///
+/// A coarse sweep over a full crank rotation brackets the nearest point on
+/// the curve, then a golden-section search refines the crank angle within
+/// that bracket to sub-step precision. Without the refinement step the
+/// residual would be a piecewise-constant argmin over the fixed-step
+/// samples, whose finite-difference derivative (used by the Jacobian
+/// below) is almost always exactly zero and gives Levenberg-Marquardt
+/// nothing to climb.
+fn closest_approach(params: &SynthesisParams, target: Point2D) -> f64 {
+    let config = FourBarConfig {
+        r1: params.r1,
+        r2: params.r2,
+        r3: params.r3,
+        r4: params.r4,
+    };
+    let coupler_point = CouplerPoint {
+        along: params.coupler_along,
+        offset: params.coupler_offset,
+    };
+    let mut linkage = FourBar::with_config(config);
+
+    let mut best = f64::INFINITY;
+    let mut best_deg = 0.0;
+    let mut theta2_deg: f64 = 0.0;
+    while theta2_deg < 360.0 {
+        if let Some(dist) = distance_at(&mut linkage, coupler_point, target, theta2_deg) {
+            if dist < best {
+                best = dist;
+                best_deg = theta2_deg;
+            }
+        }
+        theta2_deg += THETA2_STEP_DEG;
+    }
+
+    if !best.is_finite() {
+        return best;
+    }
+
+    let mut lo = best_deg - THETA2_STEP_DEG;
+    let mut hi = best_deg + THETA2_STEP_DEG;
+    let mut x1 = hi - GOLDEN_RATIO * (hi - lo);
+    let mut x2 = lo + GOLDEN_RATIO * (hi - lo);
+    let mut f1 = distance_at(&mut linkage, coupler_point, target, x1).unwrap_or(f64::INFINITY);
+    let mut f2 = distance_at(&mut linkage, coupler_point, target, x2).unwrap_or(f64::INFINITY);
+    for _ in 0..GOLDEN_SECTION_ITERATIONS {
+        if f1 < f2 {
+            hi = x2;
+            x2 = x1;
+            f2 = f1;
+            x1 = hi - GOLDEN_RATIO * (hi - lo);
+            f1 = distance_at(&mut linkage, coupler_point, target, x1).unwrap_or(f64::INFINITY);
+        } else {
+            lo = x1;
+            x1 = x2;
+            f1 = f2;
+            x2 = lo + GOLDEN_RATIO * (hi - lo);
+            f2 = distance_at(&mut linkage, coupler_point, target, x2).unwrap_or(f64::INFINITY);
+        }
+    }
+
+    best.min(f1.min(f2))
+}
+
+fn residuals(v: &[f64; NUM_PARAMS], targets: &[Point2D]) -> Vec<f64> {
+    let params = from_vec(v);
+    targets
+        .iter()
+        .map(|&target| closest_approach(&params, target))
+        .collect()
+}
+
+/// Finite-difference Jacobian of the residual vector w.r.t. the 6 params
+fn numeric_jacobian(v: &[f64; NUM_PARAMS], targets: &[Point2D], r0: &[f64]) -> Vec<[f64; NUM_PARAMS]> {
+    const EPS: f64 = 1e-6;
+    let mut jac = vec![[0.0; NUM_PARAMS]; targets.len()];
+
+    for col in 0..NUM_PARAMS {
+        let mut v_pert = *v;
+        let step = EPS * v_pert[col].abs().max(1.0);
+        v_pert[col] += step;
+        let r_pert = residuals(&v_pert, targets);
+        for row in 0..targets.len() {
+            jac[row][col] = (r_pert[row] - r0[row]) / step;
+        }
+    }
+    jac
+}
+
+/// Solve an `n x n` linear system by Gaussian elimination with partial
+/// pivoting. Returns `None` if the matrix is singular.
+fn solve_n(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&r1, &r2| a[r1][col].abs().partial_cmp(&a[r2][col].abs()).unwrap())?;
+        if a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+
+        let pivot_row = a[col].clone();
+        for row in (col + 1)..n {
+            let factor = a[row][col] / pivot_row[col];
+            for (k, pivot_val) in pivot_row.iter().enumerate().skip(col) {
+                a[row][k] -= factor * pivot_val;
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let mut sum = b[row];
+        for (k, &xk) in x.iter().enumerate().skip(row + 1) {
+            sum -= a[row][k] * xk;
+        }
+        x[row] = sum / a[row][row];
+    }
+    Some(x)
+}
+
+/// Fit link lengths and a coupler point so the coupler curve passes
+/// through `targets`, starting from `initial` and refining with
+/// Levenberg-Marquardt: `(J^T J + lambda * diag(J^T J)) delta = -J^T r`,
+/// growing `lambda` when a step raises the residual and shrinking it on
+/// success.
+pub fn synthesize(
+    targets: &[Point2D],
+    initial: SynthesisParams,
+    options: SynthesisOptions,
+) -> Result<SynthesisResult, String> {
+    if targets.is_empty() {
+        return Err("synthesize requires at least one target point".to_string());
+    }
+
+    let mut v = to_vec(initial);
+    let mut r = residuals(&v, targets);
+    let mut cost: f64 = r.iter().map(|x| x * x).sum();
+    let mut lambda = 1e-3;
+
+    for _iter in 0..options.max_iterations {
+        if cost.sqrt() < 1e-9 {
+            break;
+        }
+
+        let jac = numeric_jacobian(&v, targets, &r);
+
+        let mut jt_j = vec![vec![0.0; NUM_PARAMS]; NUM_PARAMS];
+        let mut jt_r = [0.0; NUM_PARAMS];
+        for row in jac.iter().zip(r.iter()) {
+            let (j_row, &r_i) = row;
+            for a in 0..NUM_PARAMS {
+                jt_r[a] += j_row[a] * r_i;
+                for b in 0..NUM_PARAMS {
+                    jt_j[a][b] += j_row[a] * j_row[b];
+                }
+            }
+        }
+
+        let mut improved = false;
+        for _backtrack in 0..30 {
+            let mut a = jt_j.clone();
+            for i in 0..NUM_PARAMS {
+                a[i][i] += lambda * jt_j[i][i].max(1e-12);
+            }
+            let neg_jt_r: Vec<f64> = jt_r.iter().map(|x| -x).collect();
+
+            let Some(delta) = solve_n(a, neg_jt_r) else {
+                lambda *= 10.0;
+                continue;
+            };
+
+            let mut v_new = v;
+            for i in 0..NUM_PARAMS {
+                v_new[i] += delta[i];
+            }
+            let r_new = residuals(&v_new, targets);
+            let cost_new: f64 = r_new.iter().map(|x| x * x).sum();
+
+            if cost_new < cost {
+                v = v_new;
+                r = r_new;
+                cost = cost_new;
+                lambda *= 0.5;
+                improved = true;
+                break;
+            } else {
+                lambda *= 10.0;
+            }
+        }
+
+        if !improved {
+            break;
+        }
+    }
+
+    let params = from_vec(&v);
+    let config = FourBarConfig {
+        r1: params.r1,
+        r2: params.r2,
+        r3: params.r3,
+        r4: params.r4,
+    };
+
+    if options.require_crank_rocker {
+        let linkage = FourBar::with_config(config);
+        if linkage.mechanism_type() != "Crank-Rocker" {
+            return Err(
+                "fitted linkage does not satisfy the requested crank-rocker constraint"
+                    .to_string(),
+            );
+        }
+    }
+
+    Ok(SynthesisResult {
+        config,
+        coupler_point: CouplerPoint {
+            along: params.coupler_along,
+            offset: params.coupler_offset,
+        },
+        rms_error: (cost / targets.len() as f64).sqrt(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_targets(params: SynthesisParams, count: usize) -> Vec<Point2D> {
+        let config = FourBarConfig {
+            r1: params.r1,
+            r2: params.r2,
+            r3: params.r3,
+            r4: params.r4,
+        };
+        let coupler_point = CouplerPoint {
+            along: params.coupler_along,
+            offset: params.coupler_offset,
+        };
+        let mut linkage = FourBar::with_config(config);
+        (0..count)
+            .map(|i| {
+                let theta2_deg = 360.0 * i as f64 / count as f64;
+                linkage.set_theta2_degrees(theta2_deg).unwrap();
+                linkage.coupler_point_position(coupler_point)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_synthesize_fits_sampled_coupler_curve() {
+        let truth = SynthesisParams {
+            r1: 6.0,
+            r2: 2.0,
+            r3: 5.0,
+            r4: 5.0,
+            coupler_along: 2.5,
+            coupler_offset: 1.0,
+        };
+        let targets = sample_targets(truth, 12);
+
+        let initial = SynthesisParams {
+            r1: 5.5,
+            r2: 2.2,
+            r3: 4.5,
+            r4: 4.7,
+            coupler_along: 2.0,
+            coupler_offset: 0.5,
+        };
+        let result = synthesize(&targets, initial, SynthesisOptions::default()).unwrap();
+
+        assert!(
+            result.rms_error < 1e-3,
+            "expected a close fit, got rms_error = {}",
+            result.rms_error
+        );
+    }
+
+    #[test]
+    fn test_synthesize_stays_put_when_seeded_at_the_answer() {
+        let truth = SynthesisParams {
+            r1: 6.0,
+            r2: 2.0,
+            r3: 5.0,
+            r4: 5.0,
+            coupler_along: 2.5,
+            coupler_offset: 1.0,
+        };
+        let targets = sample_targets(truth, 12);
+
+        let result = synthesize(&targets, truth, SynthesisOptions::default()).unwrap();
+        assert!(result.rms_error < 1e-9);
+    }
+
+    #[test]
+    fn test_synthesize_rejects_empty_targets() {
+        let result = synthesize(&[], SynthesisParams {
+            r1: 6.0,
+            r2: 2.0,
+            r3: 5.0,
+            r4: 5.0,
+            coupler_along: 2.5,
+            coupler_offset: 1.0,
+        }, SynthesisOptions::default());
+        assert!(result.is_err());
+    }
+}